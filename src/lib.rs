@@ -1,4 +1,7 @@
-//! A simple object pool that blocks when taking an item out.
+//! A simple object pool, with [`Pool::take`] blocking while the pool is empty, and
+//! alternatives for callers that can't wait indefinitely on a [`Condvar`](std::sync::Condvar):
+//! `try_take` returns at once, `take_timeout` waits at most a given duration, and the
+//! feature-gated `take_async` waits without parking the thread.
 //!
 //! ```
 //! use block_pool::Pool;
@@ -12,45 +15,337 @@
 use std::{
     collections::VecDeque,
     ops::{Deref, DerefMut},
-    sync::{Condvar, Mutex},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
 };
 
 /// Container for objects that can be taken out.
 pub struct Pool<T> {
-    items: Mutex<VecDeque<T>>,
+    state: Mutex<State<T>>,
     value_returned: Condvar,
+    reset: Option<Reset<T>>,
+    factory: Option<Box<dyn Fn() -> T + Send + Sync>>,
+    max: Option<usize>,
+}
+
+struct State<T> {
+    items: VecDeque<T>,
+    outstanding: usize,
+    #[cfg(feature = "async")]
+    waiters: VecDeque<Arc<WakerSlot>>,
+}
+
+/// A single [`take_async`](Pool::take_async) registration. Re-polling the same [`TakeFuture`]
+/// replaces the waker in place rather than enqueueing a new entry, and dropping the future clears
+/// the slot so a stale/cancelled registration is skipped instead of swallowing a wakeup meant for
+/// another waiter.
+#[cfg(feature = "async")]
+struct WakerSlot {
+    waker: Mutex<Option<Waker>>,
+    enqueued: AtomicBool,
+}
+
+/// A reset closure that can fail; see [`Pool::with_fallible_reset`].
+type FallibleResetFn<T> =
+    dyn Fn(&mut T) -> Result<(), Box<dyn std::error::Error + Send + Sync>> + Send + Sync;
+
+/// The reset behavior run on an item right before it rejoins the pool.
+enum Reset<T> {
+    Infallible(Box<dyn Fn(&mut T) + Send + Sync>),
+    Fallible(Box<FallibleResetFn<T>>),
 }
 
 impl<T> Pool<T> {
     /// Construct a new Pool with the items from the iterator.
     pub fn new(items: impl IntoIterator<Item = T>) -> Self {
         Pool {
-            items: Mutex::new(items.into_iter().collect()),
+            state: Mutex::new(State {
+                items: items.into_iter().collect(),
+                outstanding: 0,
+                #[cfg(feature = "async")]
+                waiters: VecDeque::new(),
+            }),
             value_returned: Condvar::new(),
+            reset: None,
+            factory: None,
+            max: None,
         }
     }
 
+    /// Construct a new Pool that runs `reset` on each item right before it is returned to the
+    /// pool, so callers don't need to remember to clear/zero state themselves.
+    ///
+    /// Chain [`Pool::with_elastic_factory`] onto the result to also grow the pool instead of
+    /// blocking when it's empty.
+    pub fn with_reset(
+        items: impl IntoIterator<Item = T>,
+        reset: impl Fn(&mut T) + Send + Sync + 'static,
+    ) -> Self {
+        Pool {
+            reset: Some(Reset::Infallible(Box::new(reset))),
+            ..Pool::new(items)
+        }
+    }
+
+    /// Construct a new Pool whose reset can fail. An item whose reset returns `Err` is discarded
+    /// instead of being returned to the pool.
+    ///
+    /// Chain [`Pool::with_elastic_factory`] onto the result to also grow the pool instead of
+    /// blocking when it's empty.
+    pub fn with_fallible_reset(
+        items: impl IntoIterator<Item = T>,
+        reset: impl Fn(&mut T) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Pool {
+            reset: Some(Reset::Fallible(Box::new(reset))),
+            ..Pool::new(items)
+        }
+    }
+
+    /// Construct a Pool that mints new items with `factory` instead of blocking when it is
+    /// empty. Pass `max` to still bound the number of outstanding items; once that many items
+    /// are checked out, `take` blocks like a regular pool until one is returned.
+    ///
+    /// Equivalent to `Pool::new(items).with_elastic_factory(factory, max)`; use that directly to
+    /// combine elastic growth with a reset hook from [`Pool::with_reset`] /
+    /// [`Pool::with_fallible_reset`].
+    pub fn with_factory(
+        items: impl IntoIterator<Item = T>,
+        factory: impl Fn() -> T + Send + Sync + 'static,
+        max: Option<usize>,
+    ) -> Self {
+        Pool::new(items).with_elastic_factory(factory, max)
+    }
+
+    /// Attach an elastic factory (and optional outstanding-items cap) to a pool already
+    /// constructed with [`Pool::with_reset`] or [`Pool::with_fallible_reset`], so a single pool
+    /// can both auto-reset returned items and mint new ones instead of blocking when empty.
+    pub fn with_elastic_factory(
+        mut self,
+        factory: impl Fn() -> T + Send + Sync + 'static,
+        max: Option<usize>,
+    ) -> Self {
+        self.factory = Some(Box::new(factory));
+        self.max = max;
+        self
+    }
+
     /// Remove an item from the pool, this will take the "oldest" item.
     ///
     /// The item will automatically get returned to the pool when the smart pointer is dropped.
     ///
-    /// There is no "resetting" that is common in other frameworks. You need to perform any
-    /// resetting on your own.
-    pub fn take(&self) -> Returnable<T> {
-        let mut lock = self.items.lock().unwrap();
+    /// There is no "resetting" that is common in other frameworks unless the pool was
+    /// constructed with [`Pool::with_reset`] or [`Pool::with_fallible_reset`].
+    pub fn take(&self) -> Returnable<'_, T> {
+        let mut lock = self.state.lock().unwrap();
         loop {
-            if let Some(value) = lock.pop_front() {
+            if let Some(value) = self.try_acquire(&mut lock) {
                 return Returnable {
                     value: Some(value),
                     pool: self,
                 };
             }
+
+            lock = self.value_returned.wait(lock).unwrap();
+        }
+    }
+
+    /// Remove an item from the pool without blocking. Returns `None` if the pool is empty (and,
+    /// for an elastic pool, no more items can be minted under `max`).
+    pub fn try_take(&self) -> Option<Returnable<'_, T>> {
+        let mut lock = self.state.lock().unwrap();
+        let value = self.try_acquire(&mut lock)?;
+        Some(Returnable {
+            value: Some(value),
+            pool: self,
+        })
+    }
+
+    /// Remove an item from the pool, waiting at most `dur` for one to become available. Returns
+    /// `None` if the deadline passes first.
+    pub fn take_timeout(&self, dur: Duration) -> Option<Returnable<'_, T>> {
+        let deadline = Instant::now() + dur;
+        let mut lock = self.state.lock().unwrap();
+        loop {
+            if let Some(value) = self.try_acquire(&mut lock) {
+                return Some(Returnable {
+                    value: Some(value),
+                    pool: self,
+                });
+            }
+
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            let (new_lock, _) = self.value_returned.wait_timeout(lock, remaining).unwrap();
+            lock = new_lock;
+        }
+    }
+
+    /// Remove an item from the pool, same as [`Pool::take`], but returns a handle that owns an
+    /// `Arc` to the pool instead of borrowing it. This lets the checked-out item move into a
+    /// spawned thread or be stored in a `'static` task.
+    pub fn take_owned(self: &Arc<Self>) -> OwnedReturnable<T> {
+        let mut lock = self.state.lock().unwrap();
+        loop {
+            if let Some(value) = self.try_acquire(&mut lock) {
+                return OwnedReturnable {
+                    value: Some(value),
+                    pool: self.clone(),
+                };
+            }
+
             lock = self.value_returned.wait(lock).unwrap();
         }
     }
 
-    fn return_(&self, value: T) {
-        self.items.lock().unwrap().push_back(value);
+    /// The number of items currently sitting in the pool, ready to be taken.
+    pub fn available(&self) -> usize {
+        self.state.lock().unwrap().items.len()
+    }
+
+    /// The number of items currently checked out of the pool (via `take`, `try_take`,
+    /// `take_timeout`, `take_owned`, or `take_async`).
+    pub fn outstanding(&self) -> usize {
+        self.state.lock().unwrap().outstanding
+    }
+
+    /// Pop an item from `items`, or mint one from the factory if under `max`. Does not block.
+    fn try_acquire(&self, lock: &mut MutexGuard<State<T>>) -> Option<T> {
+        if let Some(value) = lock.items.pop_front() {
+            lock.outstanding += 1;
+            return Some(value);
+        }
+
+        if let Some(factory) = &self.factory {
+            if self.max.is_none_or(|max| lock.outstanding < max) {
+                lock.outstanding += 1;
+                return Some(factory());
+            }
+        }
+
+        None
+    }
+
+    /// Account for an item that was [`Returnable::detach`]ed rather than returned, so an elastic
+    /// pool knows it can mint a replacement within `max`.
+    fn forget(&self) {
+        let mut lock = self.state.lock().unwrap();
+        lock.outstanding -= 1;
+        self.wake_one(&mut lock);
+        drop(lock);
+
+        self.value_returned.notify_one();
+    }
+
+    /// Reset (if configured), account for, and push `value` back into the pool. Used by both the
+    /// borrowed and owned returnable wrappers.
+    fn return_(&self, mut value: T) {
+        let keep = match &self.reset {
+            None => true,
+            Some(Reset::Infallible(reset)) => {
+                reset(&mut value);
+                true
+            }
+            Some(Reset::Fallible(reset)) => reset(&mut value).is_ok(),
+        };
+
+        let mut lock = self.state.lock().unwrap();
+        lock.outstanding -= 1;
+        if keep {
+            lock.items.push_back(value);
+        }
+        self.wake_one(&mut lock);
+        drop(lock);
+
+        self.value_returned.notify_one();
+    }
+
+    /// Wake one task blocked in [`Pool::take_async`], if any are waiting. Skips over
+    /// already-woken or cancelled slots instead of stopping at the first one, so a genuinely
+    /// still-waiting task isn't starved behind a stale registration.
+    #[cfg(feature = "async")]
+    fn wake_one(&self, lock: &mut MutexGuard<State<T>>) {
+        while let Some(slot) = lock.waiters.pop_front() {
+            slot.enqueued.store(false, Ordering::Release);
+            if let Some(waker) = slot.waker.lock().unwrap().take() {
+                waker.wake();
+                return;
+            }
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    fn wake_one(&self, _lock: &mut MutexGuard<State<T>>) {}
+}
+
+#[cfg(feature = "async")]
+impl<T> Pool<T> {
+    /// Remove an item from the pool without blocking an OS thread. Registers the calling task's
+    /// waker and returns [`Poll::Pending`] until an item is available, instead of parking on a
+    /// [`Condvar`] like [`Pool::take`].
+    pub async fn take_async(&self) -> Returnable<'_, T> {
+        TakeFuture {
+            pool: self,
+            slot: None,
+        }
+        .await
+    }
+}
+
+#[cfg(feature = "async")]
+struct TakeFuture<'p, T> {
+    pool: &'p Pool<T>,
+    slot: Option<Arc<WakerSlot>>,
+}
+
+#[cfg(feature = "async")]
+impl<'p, T> Future for TakeFuture<'p, T> {
+    type Output = Returnable<'p, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut lock = this.pool.state.lock().unwrap();
+        if let Some(value) = this.pool.try_acquire(&mut lock) {
+            return Poll::Ready(Returnable {
+                value: Some(value),
+                pool: this.pool,
+            });
+        }
+
+        let slot = this.slot.get_or_insert_with(|| {
+            Arc::new(WakerSlot {
+                waker: Mutex::new(None),
+                enqueued: AtomicBool::new(false),
+            })
+        });
+        *slot.waker.lock().unwrap() = Some(cx.waker().clone());
+        if slot
+            .enqueued
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            lock.waiters.push_back(slot.clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'p, T> Drop for TakeFuture<'p, T> {
+    fn drop(&mut self) {
+        if let Some(slot) = &self.slot {
+            *slot.waker.lock().unwrap() = None;
+        }
     }
 }
 
@@ -63,10 +358,21 @@ pub struct Returnable<'p, T> {
     pool: &'p Pool<T>,
 }
 
+impl<'p, T> Returnable<'p, T> {
+    /// Permanently remove this item from the pool instead of returning it on drop. Useful when
+    /// the item is known to be bad (a poisoned buffer, a closed connection) and shouldn't be
+    /// handed to the next caller.
+    pub fn detach(mut self) -> T {
+        self.pool.forget();
+        self.value.take().unwrap()
+    }
+}
+
 impl<'p, T> Drop for Returnable<'p, T> {
     fn drop(&mut self) {
-        self.pool.return_(self.value.take().unwrap());
-        self.pool.value_returned.notify_one();
+        if let Some(value) = self.value.take() {
+            self.pool.return_(value);
+        }
     }
 }
 
@@ -83,3 +389,201 @@ impl<'p, T> DerefMut for Returnable<'p, T> {
         self.value.as_mut().unwrap()
     }
 }
+
+/// A smart pointer that holds an object taken from a pool via [`Pool::take_owned`].
+///
+/// Unlike [`Returnable`], this holds an `Arc<Pool<T>>` rather than borrowing the pool, so it can
+/// be moved into a spawned thread or stored in a `'static` task. Returns the object to the pool
+/// when dropped.
+pub struct OwnedReturnable<T> {
+    // Only Option so that we can take ownership of the value in Drop.
+    value: Option<T>,
+    pool: Arc<Pool<T>>,
+}
+
+impl<T> OwnedReturnable<T> {
+    /// Permanently remove this item from the pool instead of returning it on drop. Useful when
+    /// the item is known to be bad (a poisoned buffer, a closed connection) and shouldn't be
+    /// handed to the next caller.
+    pub fn detach(mut self) -> T {
+        self.pool.forget();
+        self.value.take().unwrap()
+    }
+}
+
+impl<T> Drop for OwnedReturnable<T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.pool.return_(value);
+        }
+    }
+}
+
+impl<T> Deref for OwnedReturnable<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for OwnedReturnable<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.value.as_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reset_runs_on_return() {
+        let pool = Pool::with_reset(vec![1], |x: &mut u32| *x = 0);
+
+        let item = pool.try_take().unwrap();
+        assert_eq!(*item, 1);
+        drop(item);
+
+        assert_eq!(*pool.try_take().unwrap(), 0);
+    }
+
+    #[test]
+    fn fallible_reset_discards_on_error() {
+        let pool = Pool::with_fallible_reset(vec![1], |x: &mut u32| {
+            if *x == 1 {
+                Err("poisoned".into())
+            } else {
+                Ok(())
+            }
+        });
+
+        let item = pool.try_take().unwrap();
+        drop(item);
+
+        assert_eq!(pool.available(), 0);
+        assert!(pool.try_take().is_none());
+    }
+
+    #[test]
+    fn factory_stops_minting_once_max_outstanding_items_are_checked_out() {
+        let pool = Pool::with_factory(Vec::<u32>::new(), || 0, Some(1));
+
+        let first = pool.try_take().expect("factory should mint a fresh item");
+        assert_eq!(pool.outstanding(), 1);
+
+        assert!(
+            pool.try_take().is_none(),
+            "pool is at its max of 1 outstanding item, so it must not mint another"
+        );
+
+        drop(first);
+        assert!(
+            pool.try_take().is_some(),
+            "returning the checked-out item should free a slot under max"
+        );
+    }
+
+    #[test]
+    fn take_timeout_expires_on_an_empty_pool() {
+        let pool: Pool<u32> = Pool::new(Vec::new());
+        assert!(pool.take_timeout(Duration::from_millis(20)).is_none());
+    }
+
+    #[test]
+    fn take_timeout_succeeds_once_an_item_is_available() {
+        let pool = Arc::new(Pool::new(vec![0u32]));
+        let pool2 = pool.clone();
+
+        let first = pool.take();
+        let handle =
+            std::thread::spawn(move || pool2.take_timeout(Duration::from_secs(1)).is_some());
+        std::thread::sleep(Duration::from_millis(20));
+        drop(first);
+
+        assert!(handle.join().unwrap());
+    }
+
+    #[test]
+    fn take_owned_moves_into_a_spawned_thread_and_returns_on_drop() {
+        let pool = Arc::new(Pool::new(vec![1]));
+
+        let handle = std::thread::spawn({
+            let pool = pool.clone();
+            move || {
+                let item = pool.take_owned();
+                assert_eq!(*item, 1);
+            }
+        });
+        handle.join().unwrap();
+
+        assert_eq!(pool.available(), 1);
+    }
+
+    #[test]
+    fn detach_prevents_the_item_from_returning_to_the_pool() {
+        let pool = Pool::new(vec![1]);
+
+        let item = pool.try_take().unwrap();
+        assert_eq!(item.detach(), 1);
+
+        assert_eq!(pool.available(), 0);
+        assert!(pool.try_take().is_none());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn take_async_wakes_when_an_item_is_returned() {
+        let pool = Arc::new(Pool::new(vec![1]));
+
+        let first = block_on(pool.take_async());
+        assert_eq!(pool.outstanding(), 1);
+
+        let pool2 = pool.clone();
+        let handle = std::thread::spawn(move || *block_on(pool2.take_async()));
+        std::thread::sleep(Duration::from_millis(20));
+        drop(first);
+
+        assert_eq!(handle.join().unwrap(), 1);
+    }
+
+    /// A minimal single-future executor, just enough to drive `take_async` in tests without
+    /// pulling in an async runtime dependency.
+    #[cfg(feature = "async")]
+    fn block_on<F: Future>(mut fut: F) -> F::Output {
+        use std::task::{Wake, Waker};
+
+        struct ThreadParker {
+            ready: Mutex<bool>,
+            condvar: Condvar,
+        }
+
+        impl Wake for ThreadParker {
+            fn wake(self: Arc<Self>) {
+                *self.ready.lock().unwrap() = true;
+                self.condvar.notify_one();
+            }
+        }
+
+        let parker = Arc::new(ThreadParker {
+            ready: Mutex::new(false),
+            condvar: Condvar::new(),
+        });
+        let waker = Waker::from(parker.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        // SAFETY: `fut` is never moved after this point.
+        let mut fut = unsafe { Pin::new_unchecked(&mut fut) };
+        loop {
+            if let Poll::Ready(value) = fut.as_mut().poll(&mut cx) {
+                return value;
+            }
+
+            let mut ready = parker.ready.lock().unwrap();
+            while !*ready {
+                ready = parker.condvar.wait(ready).unwrap();
+            }
+            *ready = false;
+        }
+    }
+}